@@ -0,0 +1,103 @@
+use std::f64::INFINITY;
+
+use crate::rand::prelude::*;
+
+use crate::geometry::Intersectable;
+use crate::geometry::Intersection;
+use crate::geometry::Intersects;
+use crate::geometry::AABB;
+
+use crate::geometry::Ray;
+
+pub enum BVHNode {
+    Leaf {
+        bounding_box: AABB,
+        intersectable: Intersectable,
+    },
+    Branch {
+        bounding_box: AABB,
+        left: Box<BVHNode>,
+        right: Box<BVHNode>,
+    },
+}
+
+impl BVHNode {
+    pub fn bounding_box(&self) -> AABB {
+        match self {
+            BVHNode::Leaf { bounding_box, .. } => *bounding_box,
+            BVHNode::Branch { bounding_box, .. } => *bounding_box,
+        }
+    }
+
+    // `None` means an empty world; rays then always miss and pick up
+    // only the background, rather than this panicking on a schema-valid
+    // but empty scene
+    pub fn build(intersectables: Vec<Intersectable>) -> Option<BVHNode> {
+        if intersectables.is_empty() {
+            return None;
+        }
+        Some(Self::build_nonempty(intersectables))
+    }
+
+    fn build_nonempty(mut intersectables: Vec<Intersectable>) -> BVHNode {
+        let mut rng = rand::thread_rng();
+        let axis = rng.gen_range(0, 3);
+        intersectables.sort_by(|a, b| {
+            let a_centroid = centroid(&a.bounding_box(), axis);
+            let b_centroid = centroid(&b.bounding_box(), axis);
+            a_centroid.partial_cmp(&b_centroid).unwrap()
+        });
+        let count = intersectables.len();
+        if count == 1 {
+            let intersectable = intersectables.pop().unwrap();
+            let bounding_box = intersectable.bounding_box();
+            return BVHNode::Leaf {
+                bounding_box,
+                intersectable,
+            };
+        }
+        let right_half = intersectables.split_off(count / 2);
+        let left = Self::build_nonempty(intersectables);
+        let right = Self::build_nonempty(right_half);
+        let bounding_box = AABB::surrounding(&left.bounding_box(), &right.bounding_box());
+        BVHNode::Branch {
+            bounding_box,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn hit<'a>(&'a self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(Intersection, &'a Intersectable)> {
+        if !self.bounding_box().hit(ray, t_min, t_max) {
+            return None;
+        }
+        // t_min/t_max are ray parameters; `Intersection.distance` is a length
+        // along the ray, so it is divided by the direction's length to compare
+        let direction_length = ray.direction.length();
+        match self {
+            BVHNode::Leaf { intersectable, .. } => intersectable
+                .intersects(ray)
+                .filter(|intersection| intersection.distance / direction_length < t_max)
+                .map(|intersection| (intersection, intersectable)),
+            BVHNode::Branch { left, right, .. } => {
+                let left_hit = left.hit(ray, t_min, t_max);
+                let closer_t_max = left_hit
+                    .as_ref()
+                    .map(|(intersection, _)| intersection.distance / direction_length)
+                    .unwrap_or(t_max);
+                let right_hit = right.hit(ray, t_min, closer_t_max);
+                right_hit.or(left_hit)
+            }
+        }
+    }
+}
+
+pub fn find_intersections<'a>(ray: &Ray, world: Option<&'a BVHNode>) -> Option<(Intersection, &'a Intersectable)> {
+    world.and_then(|node| node.hit(ray, 0.01, INFINITY))
+}
+
+fn centroid(bounding_box: &AABB, axis: usize) -> f64 {
+    let min = [bounding_box.min.0, bounding_box.min.1, bounding_box.min.2];
+    let max = [bounding_box.max.0, bounding_box.max.1, bounding_box.max.2];
+    0.5 * (min[axis] + max[axis])
+}
@@ -0,0 +1,70 @@
+use std::f64::consts::PI;
+
+use crate::linalg::cross;
+use crate::linalg::Vec3D;
+
+use crate::geometry::Ray;
+
+const ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+pub struct Camera {
+    origin: Vec3D,
+    lower_left_corner: Vec3D,
+    horizontal: Vec3D,
+    vertical: Vec3D,
+    u: Vec3D,
+    v: Vec3D,
+    lens_radius: f64,
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+}
+
+impl Camera {
+    pub fn new(
+        position: Vec3D,
+        look_at: Vec3D,
+        up: Vec3D,
+        fov: f64,
+        aperture: f64,
+        shutter_open: f64,
+        shutter_close: f64,
+    ) -> Camera {
+        let theta = fov * PI / 180.0;
+        let viewport_height = 2.0 * (theta / 2.0).tan();
+        let viewport_width = ASPECT_RATIO * viewport_height;
+
+        let w = (position - look_at).l2_normalize();
+        let u = cross(&up, &w).l2_normalize();
+        let v = cross(&w, &u);
+
+        let horizontal = viewport_width * u;
+        let vertical = viewport_height * v;
+        let lower_left_corner = position - horizontal / 2.0 - vertical / 2.0 - w;
+
+        Camera {
+            origin: position,
+            lower_left_corner,
+            horizontal,
+            vertical,
+            u,
+            v,
+            lens_radius: aperture / 2.0,
+            shutter_open,
+            shutter_close,
+        }
+    }
+
+    pub fn prime_ray(&self, row_frac: f64, col_frac: f64, time: f64) -> Ray {
+        let lens_point = self.lens_radius * Vec3D::random_unit_disk_vector();
+        let lens_offset = self.u * lens_point.0 + self.v * lens_point.1;
+        let direction = self.lower_left_corner + col_frac * self.horizontal
+            + (1.0 - row_frac) * self.vertical
+            - self.origin
+            - lens_offset;
+        Ray {
+            origin: self.origin + lens_offset,
+            direction,
+            time,
+        }
+    }
+}
@@ -1,17 +1,21 @@
 use std::option::Option;
-use std::f64::INFINITY;
 use std::f64::consts::PI;
 
 use crate::rand::prelude::*;
 
-use crate::linalg::Vec3D;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::linalg::cross;
 use crate::linalg::dot;
+use crate::linalg::Vec3D;
 
 use crate::materials::Material;
 
 pub struct Ray {
     pub origin: Vec3D,
     pub direction: Vec3D,
+    pub time: f64,
 }
 
 impl Ray {
@@ -30,10 +34,58 @@ pub struct Intersection {
 
 pub trait Intersects {
     fn intersects(&self, ray: &Ray) -> Option<Intersection>;
-    fn surface_normal(&self, point: Vec3D) -> Vec3D;
+    // `time` matters for intersectables whose geometry moves (see
+    // `MovingSphere`); stationary intersectables just ignore it
+    fn surface_normal(&self, point: Vec3D, time: f64) -> Vec3D;
     fn material(&self) -> Material;
+    fn bounding_box(&self) -> AABB;
 }
 
+#[derive(Copy, Clone)]
+pub struct AABB {
+    pub min: Vec3D,
+    pub max: Vec3D,
+}
+
+impl AABB {
+    pub fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        let origin = [ray.origin.0, ray.origin.1, ray.origin.2];
+        let direction = [ray.direction.0, ray.direction.1, ray.direction.2];
+        let min = [self.min.0, self.min.1, self.min.2];
+        let max = [self.max.0, self.max.1, self.max.2];
+        for axis in 0..3 {
+            let mut t0 = (min[axis] - origin[axis]) / direction[axis];
+            let mut t1 = (max[axis] - origin[axis]) / direction[axis];
+            if direction[axis] < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn surrounding(a: &AABB, b: &AABB) -> AABB {
+        let min = Vec3D(
+            a.min.0.min(b.min.0),
+            a.min.1.min(b.min.1),
+            a.min.2.min(b.min.2),
+        );
+        let max = Vec3D(
+            a.max.0.max(b.max.0),
+            a.max.1.max(b.max.1),
+            a.max.2.max(b.max.2),
+        );
+        AABB { min, max }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Sphere {
     pub origin: Vec3D,
     pub radius: f64,
@@ -53,7 +105,7 @@ impl Sphere {
 }
 
 impl Intersects for Sphere {
-    fn surface_normal(&self, point: Vec3D) -> Vec3D {
+    fn surface_normal(&self, point: Vec3D, _time: f64) -> Vec3D {
         (point - self.origin).l2_normalize()
     }
 
@@ -75,7 +127,7 @@ impl Intersects for Sphere {
         }
         let t = if t0 >= 0.01 { t0 }  else { t1 };
         let point = ray.at(t);
-        let surface_normal = self.surface_normal(point);
+        let surface_normal = self.surface_normal(point, ray.time);
         let mut inside = false;
         if dot(&ray.direction, &surface_normal) > 0.0 {
             inside = true;
@@ -94,54 +146,275 @@ impl Intersects for Sphere {
     fn material(&self) -> Material {
         self.material
     }
+
+    fn bounding_box(&self) -> AABB {
+        let radius_vec = Vec3D(self.radius, self.radius, self.radius);
+        AABB {
+            min: self.origin - radius_vec,
+            max: self.origin + radius_vec,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MovingSphere {
+    pub origin0: Vec3D,
+    pub origin1: Vec3D,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl MovingSphere {
+    pub fn center(&self, time: f64) -> Vec3D {
+        let fraction = (time - self.time0) / (self.time1 - self.time0);
+        self.origin0 + fraction * (self.origin1 - self.origin0)
+    }
+}
+
+impl Intersects for MovingSphere {
+    fn surface_normal(&self, point: Vec3D, time: f64) -> Vec3D {
+        (point - self.center(time)).l2_normalize()
+    }
+
+    fn intersects(&self, ray: &Ray) -> Option<Intersection> {
+        // using quadratic formula against the time-interpolated center
+        let center = self.center(ray.time);
+        let sphere_to_ray = ray.origin - center;
+        let a = dot(&ray.direction, &ray.direction);
+        let h = dot(&sphere_to_ray, &ray.direction);
+        let c = dot(&sphere_to_ray, &sphere_to_ray) - self.radius * self.radius;
+        let discriminant = (h * h) - (a * c);
+        if discriminant < 0.0 {
+            return None;
+        }
+        let discriminant_sqrt = discriminant.sqrt();
+        let t0 = (-h - discriminant_sqrt) / a;
+        let t1 = (-h + discriminant_sqrt) / a;
+        if t0 < 0.01 && t1 < 0.01 {
+            return None;
+        }
+        let t = if t0 >= 0.01 { t0 } else { t1 };
+        let point = ray.at(t);
+        let surface_normal = self.surface_normal(point, ray.time);
+        let mut inside = false;
+        if dot(&ray.direction, &surface_normal) > 0.0 {
+            inside = true;
+        }
+        let local_normal = if inside { -surface_normal } else { surface_normal };
+        let distance = (point - ray.origin).length();
+        let intersection = Intersection {
+            point: point,
+            distance: distance,
+            local_normal: local_normal,
+            inside: inside,
+        };
+        Some(intersection)
+    }
+
+    fn material(&self) -> Material {
+        self.material
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let radius_vec = Vec3D(self.radius, self.radius, self.radius);
+        let box0 = AABB {
+            min: self.origin0 - radius_vec,
+            max: self.origin0 + radius_vec,
+        };
+        let box1 = AABB {
+            min: self.origin1 - radius_vec,
+            max: self.origin1 + radius_vec,
+        };
+        AABB::surrounding(&box0, &box1)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Triangle {
+    pub v0: Vec3D,
+    pub v1: Vec3D,
+    pub v2: Vec3D,
+    pub material: Material,
+}
+
+fn triangle_normal(v0: Vec3D, v1: Vec3D, v2: Vec3D) -> Vec3D {
+    cross(&(v1 - v0), &(v2 - v0)).l2_normalize()
+}
+
+impl Intersects for Triangle {
+    fn surface_normal(&self, _point: Vec3D, _time: f64) -> Vec3D {
+        triangle_normal(self.v0, self.v1, self.v2)
+    }
+
+    fn intersects(&self, ray: &Ray) -> Option<Intersection> {
+        // Moller-Trumbore ray-triangle intersection
+        let edge1 = self.v1 - self.v0;
+        let edge2 = self.v2 - self.v0;
+        let h = cross(&ray.direction, &edge2);
+        let a = dot(&edge1, &h);
+        if a.abs() < 1e-8 {
+            // ray is parallel to the triangle
+            return None;
+        }
+        let f = 1.0 / a;
+        let s = ray.origin - self.v0;
+        let u = f * dot(&s, &h);
+        if u < 0.0 || u > 1.0 {
+            return None;
+        }
+        let q = cross(&s, &edge1);
+        let v = f * dot(&ray.direction, &q);
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = f * dot(&edge2, &q);
+        if t <= 0.01 {
+            return None;
+        }
+        let point = ray.at(t);
+        let surface_normal = self.surface_normal(point, ray.time);
+        let mut inside = false;
+        if dot(&ray.direction, &surface_normal) > 0.0 {
+            inside = true;
+        }
+        let local_normal = if inside { -surface_normal } else { surface_normal };
+        let distance = (point - ray.origin).length();
+        Some(Intersection {
+            point: point,
+            distance: distance,
+            local_normal: local_normal,
+            inside: inside,
+        })
+    }
+
+    fn material(&self) -> Material {
+        self.material
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let min = Vec3D(
+            self.v0.0.min(self.v1.0).min(self.v2.0),
+            self.v0.1.min(self.v1.1).min(self.v2.1),
+            self.v0.2.min(self.v1.2).min(self.v2.2),
+        );
+        let max = Vec3D(
+            self.v0.0.max(self.v1.0).max(self.v2.0),
+            self.v0.1.max(self.v1.1).max(self.v2.1),
+            self.v0.2.max(self.v1.2).max(self.v2.2),
+        );
+        AABB { min, max }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TriangleMesh {
+    pub triangles: Vec<Triangle>,
+    pub material: Material,
+}
+
+impl TriangleMesh {
+    pub fn new(vertices: Vec<(Vec3D, Vec3D, Vec3D)>, material: Material) -> TriangleMesh {
+        let triangles = vertices
+            .into_iter()
+            .map(|(v0, v1, v2)| Triangle { v0, v1, v2, material })
+            .collect();
+        TriangleMesh { triangles, material }
+    }
+}
+
+impl Intersects for TriangleMesh {
+    fn surface_normal(&self, point: Vec3D, time: f64) -> Vec3D {
+        self.triangles
+            .iter()
+            .min_by(|a, b| {
+                let a_distance = (point - a.v0).length_squared();
+                let b_distance = (point - b.v0).length_squared();
+                a_distance.partial_cmp(&b_distance).unwrap()
+            })
+            .map(|triangle| triangle.surface_normal(point, time))
+            .unwrap_or(Vec3D(0.0, 0.0, 0.0))
+    }
+
+    fn intersects(&self, ray: &Ray) -> Option<Intersection> {
+        let mut closest: Option<Intersection> = None;
+        for triangle in &self.triangles {
+            if let Some(intersection) = triangle.intersects(ray) {
+                let is_closer = closest
+                    .map(|current| intersection.distance < current.distance)
+                    .unwrap_or(true);
+                if is_closer {
+                    closest = Some(intersection);
+                }
+            }
+        }
+        closest
+    }
+
+    fn material(&self) -> Material {
+        self.material
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let mut boxes = self.triangles.iter().map(|triangle| triangle.bounding_box());
+        match boxes.next() {
+            Some(first) => boxes.fold(first, |acc, bounding_box| AABB::surrounding(&acc, &bounding_box)),
+            // an empty mesh has no extent and is never hit, same as its intersects()
+            None => AABB {
+                min: Vec3D(0.0, 0.0, 0.0),
+                max: Vec3D(0.0, 0.0, 0.0),
+            },
+        }
+    }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Intersectable {
     Sphere(Sphere),
+    MovingSphere(MovingSphere),
+    Triangle(Triangle),
+    TriangleMesh(TriangleMesh),
 }
 
 impl Intersects for Intersectable {
-    fn surface_normal(&self, point: Vec3D) -> Vec3D {
+    fn surface_normal(&self, point: Vec3D, time: f64) -> Vec3D {
         match self {
-            Intersectable::Sphere(s) => s.surface_normal(point),
+            Intersectable::Sphere(s) => s.surface_normal(point, time),
+            Intersectable::MovingSphere(s) => s.surface_normal(point, time),
+            Intersectable::Triangle(t) => t.surface_normal(point, time),
+            Intersectable::TriangleMesh(m) => m.surface_normal(point, time),
         }
     }
 
     fn intersects(&self, ray: &Ray) -> Option<Intersection> {
         match self {
             Intersectable::Sphere(s) => s.intersects(ray),
+            Intersectable::MovingSphere(s) => s.intersects(ray),
+            Intersectable::Triangle(t) => t.intersects(ray),
+            Intersectable::TriangleMesh(m) => m.intersects(ray),
         }
     }
 
     fn material(&self) -> Material {
         match self {
             Intersectable::Sphere(s) => s.material(),
+            Intersectable::MovingSphere(s) => s.material(),
+            Intersectable::Triangle(t) => t.material(),
+            Intersectable::TriangleMesh(m) => m.material(),
         }
     }
-}
 
-pub fn first_intersection<'a>(intersections: Vec<Option<Intersection>>,
-                              intersectables: &'a Vec<&Intersectable>)
-                              -> Option<(Intersection, &'a Intersectable)> {
-    let num_objects = intersectables.len() as usize;
-    let mut closest_distance = INFINITY;
-    let mut closest_intersectable = intersectables[0];
-    let mut closest_intersection = intersections[0];
-    for i in 0..num_objects {
-        let result = intersections[i];
-        match result {
-            Some(intersection) => {
-                if intersection.distance < closest_distance {
-                    closest_distance = intersection.distance;
-                    closest_intersectable = intersectables[i];
-                    closest_intersection = intersections[i];
-                }
-            },
-            None => {}
+    fn bounding_box(&self) -> AABB {
+        match self {
+            Intersectable::Sphere(s) => s.bounding_box(),
+            Intersectable::MovingSphere(s) => s.bounding_box(),
+            Intersectable::Triangle(t) => t.bounding_box(),
+            Intersectable::TriangleMesh(m) => m.bounding_box(),
         }
     }
-    if closest_distance == INFINITY {
-        return None;
-    }
-    Some((closest_intersection?, closest_intersectable))
 }
+
+// the linear scan over all intersectables has been replaced by a BVH
+// traversal; see `crate::bvh::find_intersections`.
@@ -2,8 +2,9 @@ use std::f64::consts::PI;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Vec3D(pub f64, pub f64, pub f64);
 
 impl Vec3D {
@@ -59,6 +60,30 @@ impl Vec3D {
         }
     }
 
+    // cosine-weighted hemisphere sample about `local_normal`, built by
+    // transforming a disk sample into an orthonormal basis around the
+    // normal instead of rejection-sampling a unit ball; this avoids the
+    // wasted rejected samples and is exactly proportional to cos(theta)
+    pub fn random_cosine_direction(local_normal: Vec3D) -> Vec3D {
+        let mut rng = rand::thread_rng();
+        let r1: f64 = rng.gen();
+        let r2: f64 = rng.gen();
+        let phi = 2.0 * PI * r1;
+        let r2_sqrt = r2.sqrt();
+        let x = phi.cos() * r2_sqrt;
+        let y = phi.sin() * r2_sqrt;
+        let z = (1.0 - r2).sqrt();
+        let normal = local_normal.l2_normalize();
+        let axis = if normal.0.abs() > 0.9 {
+            Vec3D(0.0, 1.0, 0.0)
+        } else {
+            Vec3D(1.0, 0.0, 0.0)
+        };
+        let tangent = cross(&axis, &normal).l2_normalize();
+        let bitangent = cross(&normal, &tangent);
+        x * tangent + y * bitangent + z * normal
+    }
+
     pub fn random_unit_disk_vector() -> Vec3D {
         loop {
             let point = Vec3D::random(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
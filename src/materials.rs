@@ -0,0 +1,21 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::colors::RGB;
+use crate::linalg::Vec3D;
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Material {
+    pub color: RGB,
+    pub surface: Surface,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Surface {
+    Diffuse,
+    Reflective,
+    FuzzyReflective(f64),
+    Refractive(f64),
+    Emissive(Vec3D),
+}
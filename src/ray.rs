@@ -11,10 +11,13 @@ use crate::colors::RGB;
 
 use crate::io::write_ppm;
 
-use crate::geometry::find_intersections;
 use crate::geometry::Intersectable;
 use crate::geometry::Intersection;
 use crate::geometry::Intersects;
+use crate::geometry::Ray;
+
+use crate::bvh::find_intersections;
+use crate::bvh::BVHNode;
 
 use crate::camera::Camera;
 
@@ -25,22 +28,15 @@ use rayon::current_num_threads;
 use rayon::prelude::*;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub struct Ray {
-    pub origin: Vec3D,
-    pub direction: Vec3D,
-}
-
-impl Ray {
-    pub fn at(&self, t: f64) -> Vec3D {
-        self.origin + t * self.direction
-    }
-}
-
-fn diffuse_bounce(intersection: &Intersection, intersectable: &Intersectable) -> Ray {
-    let bounce_vector = intersection.local_normal + Vec3D::random_unit_vector();
+fn diffuse_bounce(intersection: &Intersection, intersectable: &Intersectable, ray: &Ray) -> Ray {
+    // cosine-weighted sampling reduces variance over the old
+    // normal-plus-random-unit-vector approximation for the same
+    // samples_per_pixel
+    let bounce_vector = Vec3D::random_cosine_direction(intersection.local_normal);
     Ray {
         origin: intersection.point,
         direction: bounce_vector,
+        time: ray.time,
     }
 }
 
@@ -51,6 +47,7 @@ fn reflect(intersection: &Intersection, intersectable: &Intersectable, ray: &Ray
     Ray {
         origin: intersection.point,
         direction: reflected,
+        time: ray.time,
     }
 }
 
@@ -68,6 +65,7 @@ fn fuzzy_reflect(intersection: &Intersection, intersectable: &Intersectable, ray
     Ray {
         origin: intersection.point,
         direction: reflected + direction_fuzz,
+        time: ray.time,
     }
 }
 
@@ -107,33 +105,38 @@ fn refract(intersection: &Intersection, intersectable: &Intersectable, ray: &Ray
     Ray {
         origin: intersection.point,
         direction: r2_per + r2_par,
+        time: ray.time,
     }
 }
 
-fn trace(ray: &Ray, world: &Vec<Intersectable>, depth: u64) -> RGB {
+fn trace(ray: &Ray, world: Option<&BVHNode>, depth: u64, background: Option<Vec3D>) -> RGB {
     // light enters the void if we hit the depth limit
     if depth <= 0 {
         return rgb(0.0, 0.0, 0.0);
     }
-    // determine if ray intersects and choose first intersection if so
-    let (intersections, result) = find_intersections(ray, world);
+    // descend the BVH and choose the closest intersection if any
+    let result = find_intersections(ray, world);
     match result {
         // calculate color at intersection point
         Some((intersection, intersectable)) => {
             let material = intersectable.material();
             let surface = material.surface;
             let material_color = material.color.to_vec3d();
+            if let Surface::Emissive(emission) = surface {
+                // emissive surfaces radiate light directly and do not scatter
+                return vec_to_rgb(emission);
+            }
             let mut traced_color = Vec3D(0.0, 0.0, 0.0);
             if let Surface::Diffuse = surface {
                 // light bounces randomly if material is diffuse,
                 // so we recurse and trace a randomly bounced ray
-                let bounced = &diffuse_bounce(&intersection, intersectable);
-                traced_color = trace(bounced, world, depth - 1).to_vec3d();
+                let bounced = &diffuse_bounce(&intersection, intersectable, ray);
+                traced_color = trace(bounced, world, depth - 1, background).to_vec3d();
             } else if let Surface::Reflective = surface {
                 // light is reflected if material is totally reflective,
                 // so we recurse and trace a reflected ray
                 let reflected = &reflect(&intersection, intersectable, ray);
-                traced_color = trace(reflected, world, depth - 1).to_vec3d();
+                traced_color = trace(reflected, world, depth - 1, background).to_vec3d();
             } else if let Surface::FuzzyReflective(fuzz) = surface {
                 // light is reflected with some random offset
                 // if material is fuzzy reflective,
@@ -141,30 +144,45 @@ fn trace(ray: &Ray, world: &Vec<Intersectable>, depth: u64) -> RGB {
                 // with a check to make sure the reflection is correct
                 let reflected = &fuzzy_reflect(&intersection, intersectable, ray);
                 if dot(&reflected.direction, &intersection.local_normal) > 0.0 {
-                    traced_color = trace(reflected, world, depth - 1).to_vec3d();
+                    traced_color = trace(reflected, world, depth - 1, background).to_vec3d();
                 }
             } else if let Surface::Refractive(r) = surface {
                 // light is refracted or reflected depending on angle,
                 // so we recurse to trace either a refracted/reflected ray
                 let refracted = &refract(&intersection, intersectable, ray);
-                traced_color = trace(refracted, world, depth - 1).to_vec3d();
+                traced_color = trace(refracted, world, depth - 1, background).to_vec3d();
             }
+            // emitted + material_color * incoming, with emitted == 0 here
             let color_vec = material_color * traced_color;
             return vec_to_rgb(color_vec);
         }
         None => {
-            let ray_direction = ray.direction.l2_normalize();
-            let height = 0.5 * (ray_direction.1 + 1.0);
-            return rgb(
-                (1.0 - height) + height * 0.5,
-                (1.0 - height) + height * 0.7,
-                1.0,
-            );
+            // rays that escape the scene pick up either the configured
+            // background radiance or the default sky gradient
+            match background {
+                Some(radiance) => vec_to_rgb(radiance),
+                None => {
+                    let ray_direction = ray.direction.l2_normalize();
+                    let height = 0.5 * (ray_direction.1 + 1.0);
+                    rgb(
+                        (1.0 - height) + height * 0.5,
+                        (1.0 - height) + height * 0.7,
+                        1.0,
+                    )
+                }
+            }
         }
     }
 }
 
-pub fn render(world: &Vec<Intersectable>, camera: &Camera, rows: usize, cols: usize, samples_per_pixel: f64) {
+pub fn render(
+    world: Option<&BVHNode>,
+    camera: &Camera,
+    rows: usize,
+    cols: usize,
+    samples_per_pixel: f64,
+    background: Option<Vec3D>,
+) {
     // construct blank image
     let mut image = vec![vec![0.0; 3]; rows * cols];
     let num_threads = current_num_threads();
@@ -197,9 +215,18 @@ pub fn render(world: &Vec<Intersectable>, camera: &Camera, rows: usize, cols: us
                 let col_rand = rng.gen::<f64>();
                 let row_frac = (row as f64 + 0.5 + row_rand) / (rows as f64);
                 let col_frac = (col as f64 + 0.5 + col_rand) / (cols as f64);
-                let ray = camera.prime_ray(row_frac, col_frac);
+                // freeze moving objects at a random instant within the
+                // shutter interval so averaging samples blurs their motion;
+                // a zero-width shutter means "no motion blur", and gen_range
+                // panics unless low < high, so skip sampling in that case
+                let time = if camera.shutter_open < camera.shutter_close {
+                    rng.gen_range(camera.shutter_open, camera.shutter_close)
+                } else {
+                    camera.shutter_open
+                };
+                let ray = camera.prime_ray(row_frac, col_frac, time);
                 // trace ray for current pixel
-                let color = trace(&ray, world, 50);
+                let color = trace(&ray, world, 50, background);
                 r += color.r;
                 g += color.g;
                 b += color.b;
@@ -0,0 +1,165 @@
+use std::fs;
+
+use serde::Deserialize;
+
+use crate::camera::Camera;
+use crate::colors::rgb;
+use crate::geometry::Intersectable;
+use crate::geometry::MovingSphere;
+use crate::geometry::Sphere;
+use crate::geometry::Triangle;
+use crate::geometry::TriangleMesh;
+use crate::linalg::Vec3D;
+use crate::materials::Material;
+use crate::materials::Surface;
+
+#[derive(Deserialize)]
+struct SceneFile {
+    camera: CameraSpec,
+    max_depth: u64,
+    samples_per_pixel: f64,
+    background: Option<Vec3D>,
+    objects: Vec<ObjectSpec>,
+}
+
+#[derive(Deserialize)]
+struct CameraSpec {
+    position: Vec3D,
+    look_at: Vec3D,
+    up: Vec3D,
+    fov: f64,
+    aperture: f64,
+    shutter_open: f64,
+    shutter_close: f64,
+}
+
+#[derive(Deserialize)]
+struct MaterialSpec {
+    color: Vec3D,
+    surface: SurfaceSpec,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SurfaceSpec {
+    Diffuse,
+    Reflective,
+    FuzzyReflective { fuzz: f64 },
+    Refractive { index: f64 },
+    Emissive { radiance: Vec3D },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ObjectSpec {
+    Sphere {
+        origin: Vec3D,
+        radius: f64,
+        material: MaterialSpec,
+    },
+    MovingSphere {
+        origin0: Vec3D,
+        origin1: Vec3D,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: MaterialSpec,
+    },
+    Triangle {
+        v0: Vec3D,
+        v1: Vec3D,
+        v2: Vec3D,
+        material: MaterialSpec,
+    },
+    TriangleMesh {
+        vertices: Vec<(Vec3D, Vec3D, Vec3D)>,
+        material: MaterialSpec,
+    },
+}
+
+impl MaterialSpec {
+    fn into_material(self) -> Material {
+        let surface = match self.surface {
+            SurfaceSpec::Diffuse => Surface::Diffuse,
+            SurfaceSpec::Reflective => Surface::Reflective,
+            SurfaceSpec::FuzzyReflective { fuzz } => Surface::FuzzyReflective(fuzz),
+            SurfaceSpec::Refractive { index } => Surface::Refractive(index),
+            SurfaceSpec::Emissive { radiance } => Surface::Emissive(radiance),
+        };
+        Material {
+            color: rgb(self.color.0, self.color.1, self.color.2),
+            surface,
+        }
+    }
+}
+
+impl ObjectSpec {
+    fn into_intersectable(self) -> Intersectable {
+        match self {
+            ObjectSpec::Sphere { origin, radius, material } => Intersectable::Sphere(Sphere {
+                origin,
+                radius,
+                material: material.into_material(),
+            }),
+            ObjectSpec::MovingSphere {
+                origin0,
+                origin1,
+                time0,
+                time1,
+                radius,
+                material,
+            } => Intersectable::MovingSphere(MovingSphere {
+                origin0,
+                origin1,
+                time0,
+                time1,
+                radius,
+                material: material.into_material(),
+            }),
+            ObjectSpec::Triangle { v0, v1, v2, material } => Intersectable::Triangle(Triangle {
+                v0,
+                v1,
+                v2,
+                material: material.into_material(),
+            }),
+            ObjectSpec::TriangleMesh { vertices, material } => {
+                Intersectable::TriangleMesh(TriangleMesh::new(vertices, material.into_material()))
+            }
+        }
+    }
+}
+
+pub struct Scene {
+    pub world: Vec<Intersectable>,
+    pub camera: Camera,
+    pub max_depth: u64,
+    pub samples_per_pixel: f64,
+    pub background: Option<Vec3D>,
+}
+
+pub fn load_scene(path: &str) -> Scene {
+    let contents = fs::read_to_string(path).expect("failed to read scene file");
+    let scene_file: SceneFile =
+        serde_json::from_str(&contents).expect("failed to parse scene file");
+    let camera = Camera::new(
+        scene_file.camera.position,
+        scene_file.camera.look_at,
+        scene_file.camera.up,
+        scene_file.camera.fov,
+        scene_file.camera.aperture,
+        scene_file.camera.shutter_open,
+        scene_file.camera.shutter_close,
+    );
+    let world = scene_file
+        .objects
+        .into_iter()
+        .map(ObjectSpec::into_intersectable)
+        .collect();
+    Scene {
+        world,
+        camera,
+        max_depth: scene_file.max_depth,
+        samples_per_pixel: scene_file.samples_per_pixel,
+        background: scene_file.background,
+    }
+}